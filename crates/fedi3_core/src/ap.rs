@@ -1569,7 +1569,8 @@ async fn hello_get(state: &ApState) -> Response<Body> {
       "username": state.cfg.username,
       "actor": actor_id,
       "core_version": env!("CARGO_PKG_VERSION"),
-      "did": did
+      "did": did,
+      "public_key_pem": state.cfg.public_key_pem
     });
     (
         StatusCode::OK,
@@ -50,6 +50,7 @@ where
         status: status.as_u16(),
         headers: headers_vec,
         body_b64: B64.encode(body),
+        chunked: false,
     }
 }
 
@@ -406,6 +406,7 @@ async fn handle_mailbox_request(
         status,
         headers: resp_headers.clone(),
         body_b64: B64.encode(format!(r#"{{"error":"{}"}}"#, msg.replace('"', "\\\""))),
+        chunked: false,
     };
 
     if req.method.to_ascii_uppercase() != "POST" {
@@ -458,6 +459,7 @@ async fn handle_mailbox_request(
                 status: 200,
                 headers: resp_headers,
                 body_b64: B64.encode(json),
+                chunked: false,
             }
         }
         "/.fedi3/mailbox/poll" => {
@@ -480,6 +482,7 @@ async fn handle_mailbox_request(
                 status: 200,
                 headers: resp_headers,
                 body_b64: B64.encode(json),
+                chunked: false,
             }
         }
         "/.fedi3/mailbox/ack" => {
@@ -501,6 +504,7 @@ async fn handle_mailbox_request(
                 status: 200,
                 headers: resp_headers,
                 body_b64: B64.encode(json),
+                chunked: false,
             }
         }
         _ => bad(req.id, 404, "not found".to_string()),
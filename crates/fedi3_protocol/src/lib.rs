@@ -5,6 +5,10 @@
 
 use serde::{Deserialize, Serialize};
 
+/// WebSocket subprotocols spoken on the relay tunnel, most preferred first.
+/// `v2` adds chunked response bodies (`RelayHttpResponseChunk`).
+pub const TUNNEL_WS_PROTOCOLS: &[&str] = &["fedi3.tunnel.v2", "fedi3.tunnel.v1"];
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RelayHttpRequest {
     pub id: String,
@@ -21,4 +25,31 @@ pub struct RelayHttpResponse {
     pub status: u16,
     pub headers: Vec<(String, String)>,
     pub body_b64: String,
+    /// When set, `body_b64` is empty and the body follows as `RelayHttpResponseChunk`s.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub chunked: bool,
+}
+
+/// Chunks of one streamed response a peer may have in flight before the relay grants
+/// more with `RelayHttpResponseCredit`.
+pub const TUNNEL_STREAM_WINDOW: u64 = 16;
+
+/// One slice of a chunked response body, sent after its `RelayHttpResponse` head.
+/// `seq` starts at 0; the last chunk has `end` set. At most `TUNNEL_STREAM_WINDOW`
+/// chunks may be sent beyond those already credited.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelayHttpResponseChunk {
+    pub id: String,
+    pub seq: u64,
+    pub body_b64: String,
+    #[serde(default)]
+    pub end: bool,
+}
+
+/// Relay -> peer: the HTTP client consumed `credit` more chunks of response `id`, so the
+/// peer may send that many more. Only sent for chunked responses.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelayHttpResponseCredit {
+    pub id: String,
+    pub credit: u64,
 }
@@ -5,10 +5,24 @@ CREATE TABLE IF NOT EXISTS users (
   username TEXT PRIMARY KEY,
   token_sha256 TEXT NOT NULL,
   created_at_ms BIGINT NOT NULL,
-  disabled BOOLEAN NOT NULL DEFAULT FALSE
+  disabled BOOLEAN NOT NULL DEFAULT FALSE,
+  base_domain TEXT
 );
 CREATE INDEX IF NOT EXISTS idx_users_username_lower ON users (lower(username));
 CREATE UNIQUE INDEX IF NOT EXISTS idx_users_username_lower_unique ON users (lower(username));
+ALTER TABLE users ADD COLUMN IF NOT EXISTS base_domain TEXT;
+
+CREATE TABLE IF NOT EXISTS user_tombstones (
+  username TEXT PRIMARY KEY,
+  deleted_at_ms BIGINT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS user_tombstones_deleted ON user_tombstones(deleted_at_ms);
+
+CREATE TABLE IF NOT EXISTS user_keys (
+  username TEXT PRIMARY KEY,
+  public_key_pem TEXT NOT NULL,
+  updated_at_ms BIGINT NOT NULL
+);
 
 CREATE TABLE IF NOT EXISTS user_cache (
   username TEXT PRIMARY KEY,
@@ -32,6 +46,14 @@ CREATE TABLE IF NOT EXISTS user_collection_cache (
   PRIMARY KEY(username, kind)
 );
 
+-- Actors each local user follows, derived from the cached `following` collection.
+CREATE TABLE IF NOT EXISTS user_follow_index (
+  username TEXT NOT NULL,
+  actor_id TEXT NOT NULL,
+  PRIMARY KEY(username, actor_id)
+);
+CREATE INDEX IF NOT EXISTS idx_user_follow_index_actor ON user_follow_index(actor_id, username);
+
 CREATE TABLE IF NOT EXISTS user_aggregate_cache (
   username TEXT PRIMARY KEY,
   followers_total BIGINT NOT NULL DEFAULT 0,
@@ -53,12 +75,50 @@ CREATE TABLE IF NOT EXISTS inbox_spool (
   body_b64 TEXT NOT NULL,
   body_len BIGINT NOT NULL,
   tries BIGINT NOT NULL DEFAULT 0,
-  activity_type TEXT NOT NULL DEFAULT ''
+  activity_type TEXT NOT NULL DEFAULT '',
+  next_attempt_ms BIGINT NOT NULL DEFAULT 0
 );
 ALTER TABLE inbox_spool ADD COLUMN IF NOT EXISTS tries BIGINT NOT NULL DEFAULT 0;
 ALTER TABLE inbox_spool ADD COLUMN IF NOT EXISTS activity_type TEXT NOT NULL DEFAULT '';
+ALTER TABLE inbox_spool ADD COLUMN IF NOT EXISTS next_attempt_ms BIGINT NOT NULL DEFAULT 0;
 CREATE INDEX IF NOT EXISTS inbox_spool_user_created ON inbox_spool(username, created_at_ms);
 CREATE INDEX IF NOT EXISTS inbox_spool_tries ON inbox_spool(username, tries, created_at_ms);
+CREATE INDEX IF NOT EXISTS inbox_spool_user_id ON inbox_spool(username, id);
+CREATE INDEX IF NOT EXISTS inbox_spool_user_next ON inbox_spool(username, next_attempt_ms);
+
+CREATE TABLE IF NOT EXISTS dead_letters (
+  id BIGSERIAL PRIMARY KEY,
+  username TEXT NOT NULL,
+  created_at_ms BIGINT NOT NULL,
+  dead_at_ms BIGINT NOT NULL,
+  method TEXT NOT NULL,
+  path TEXT NOT NULL,
+  query TEXT NOT NULL,
+  headers_json TEXT NOT NULL,
+  body_b64 TEXT NOT NULL,
+  body_len BIGINT NOT NULL,
+  tries BIGINT NOT NULL DEFAULT 0,
+  activity_type TEXT NOT NULL DEFAULT '',
+  reason TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS dead_letters_user_id ON dead_letters(username, id);
+CREATE INDEX IF NOT EXISTS dead_letters_dead_at ON dead_letters(dead_at_ms);
+
+CREATE TABLE IF NOT EXISTS telemetry_issues (
+  fingerprint TEXT PRIMARY KEY,
+  issue_number BIGINT NOT NULL,
+  pending_count BIGINT NOT NULL DEFAULT 0,
+  updated_at_ms BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS delivery_receipts (
+  activity_id TEXT NOT NULL,
+  username TEXT NOT NULL,
+  status TEXT NOT NULL,
+  updated_at_ms BIGINT NOT NULL,
+  PRIMARY KEY(activity_id, username)
+);
+CREATE INDEX IF NOT EXISTS delivery_receipts_updated ON delivery_receipts(updated_at_ms);
 
 CREATE TABLE IF NOT EXISTS ap_peer_compat_policy (
   host TEXT NOT NULL,
@@ -77,6 +137,14 @@ CREATE TABLE IF NOT EXISTS relay_registry (
   sign_pubkey_b64 TEXT NULL
 );
 CREATE INDEX IF NOT EXISTS idx_relay_registry_seen ON relay_registry(last_seen_ms DESC);
+ALTER TABLE relay_registry ADD COLUMN IF NOT EXISTS prev_sign_pubkey_b64 TEXT;
+ALTER TABLE relay_registry ADD COLUMN IF NOT EXISTS prev_sign_valid_until_ms BIGINT;
+
+CREATE TABLE IF NOT EXISTS relay_key_pins (
+  relay_url TEXT PRIMARY KEY,
+  sign_pubkey_b64 TEXT NOT NULL,
+  pinned_at_ms BIGINT NOT NULL
+);
 
 CREATE TABLE IF NOT EXISTS relay_meta (
   key TEXT PRIMARY KEY,
@@ -125,6 +193,33 @@ CREATE TABLE IF NOT EXISTS move_notices (
 );
 CREATE INDEX IF NOT EXISTS idx_move_notices_created ON move_notices(created_at_ms DESC);
 
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+  username TEXT NOT NULL,
+  scope TEXT NOT NULL,
+  idem_key TEXT NOT NULL,
+  request_hash TEXT NOT NULL,
+  status BIGINT NOT NULL,
+  content_type TEXT NOT NULL,
+  body TEXT NOT NULL,
+  created_at_ms BIGINT NOT NULL,
+  PRIMARY KEY(username, scope, idem_key)
+);
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created ON idempotency_keys(created_at_ms);
+
+CREATE TABLE IF NOT EXISTS move_notice_nonces (
+  origin TEXT NOT NULL,
+  nonce TEXT NOT NULL,
+  ts_ms BIGINT NOT NULL,
+  seen_at_ms BIGINT NOT NULL,
+  PRIMARY KEY(origin, nonce)
+);
+CREATE INDEX IF NOT EXISTS idx_move_notice_nonces_seen ON move_notice_nonces(seen_at_ms);
+
+CREATE TABLE IF NOT EXISTS move_notice_latest (
+  username TEXT PRIMARY KEY,
+  ts_ms BIGINT NOT NULL
+);
+
 CREATE TABLE IF NOT EXISTS move_notice_fanout (
   notice_id TEXT NOT NULL,
   relay_url TEXT NOT NULL,
@@ -144,6 +239,19 @@ CREATE TABLE IF NOT EXISTS media_items (
   created_at_ms BIGINT NOT NULL
 );
 CREATE INDEX IF NOT EXISTS idx_media_user_created ON media_items(username, created_at_ms DESC);
+ALTER TABLE media_items ADD COLUMN IF NOT EXISTS description TEXT;
+ALTER TABLE media_items ADD COLUMN IF NOT EXISTS focus TEXT;
+ALTER TABLE media_items ADD COLUMN IF NOT EXISTS visibility TEXT NOT NULL DEFAULT 'public';
+
+CREATE TABLE IF NOT EXISTS media_proxy_cache (
+  url_hash TEXT PRIMARY KEY,
+  url TEXT NOT NULL,
+  storage_key TEXT NOT NULL,
+  media_type TEXT NOT NULL,
+  size BIGINT NOT NULL,
+  created_at_ms BIGINT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_media_proxy_created ON media_proxy_cache(created_at_ms);
 
 CREATE TABLE IF NOT EXISTS user_backups (
   username TEXT PRIMARY KEY,
@@ -165,6 +273,26 @@ CREATE TABLE IF NOT EXISTS user_backups_history (
 );
 CREATE INDEX IF NOT EXISTS idx_user_backups_hist_user_created ON user_backups_history(username, created_at_ms DESC);
 
+CREATE TABLE IF NOT EXISTS backup_upload_sessions (
+  session_id TEXT PRIMARY KEY,
+  username TEXT NOT NULL,
+  content_type TEXT NOT NULL,
+  meta_json TEXT NULL,
+  received_bytes BIGINT NOT NULL DEFAULT 0,
+  created_at_ms BIGINT NOT NULL,
+  updated_at_ms BIGINT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_backup_upload_sessions_user ON backup_upload_sessions(username);
+CREATE INDEX IF NOT EXISTS idx_backup_upload_sessions_updated ON backup_upload_sessions(updated_at_ms);
+
+CREATE TABLE IF NOT EXISTS backup_upload_parts (
+  session_id TEXT NOT NULL,
+  offset_bytes BIGINT NOT NULL,
+  size_bytes BIGINT NOT NULL,
+  storage_key TEXT NOT NULL,
+  PRIMARY KEY(session_id, offset_bytes)
+);
+
 CREATE TABLE IF NOT EXISTS relay_notes (
   note_id TEXT PRIMARY KEY,
   actor_id TEXT NULL,
@@ -185,6 +313,19 @@ CREATE INDEX IF NOT EXISTS idx_relay_notes_actor ON relay_notes(actor_id);
 CREATE INDEX IF NOT EXISTS idx_relay_notes_published ON relay_notes(published_ms DESC);
 CREATE INDEX IF NOT EXISTS idx_relay_notes_search_tsv ON relay_notes USING GIN (search_tsv);
 
+CREATE TABLE IF NOT EXISTS relay_note_bodies (
+  note_id TEXT PRIMARY KEY,
+  note_json TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS relay_note_sources (
+  note_id TEXT NOT NULL,
+  relay_url TEXT NOT NULL,
+  seen_ms BIGINT NOT NULL,
+  PRIMARY KEY(note_id, relay_url)
+);
+CREATE INDEX IF NOT EXISTS idx_relay_note_sources_relay ON relay_note_sources(relay_url);
+
 CREATE TABLE IF NOT EXISTS relay_note_tags (
   note_id TEXT NOT NULL,
   tag TEXT NOT NULL,
@@ -274,12 +415,16 @@ CREATE TABLE IF NOT EXISTS relay_object_state (
   tombstone BOOLEAN NOT NULL DEFAULT FALSE,
   version_ms BIGINT NOT NULL,
   last_event_id BIGINT NULL,
-  updated_at_ms BIGINT NOT NULL
+  updated_at_ms BIGINT NOT NULL,
+  deleted_at_ms BIGINT NULL
 );
+ALTER TABLE relay_object_state ADD COLUMN IF NOT EXISTS deleted_at_ms BIGINT NULL;
 CREATE INDEX IF NOT EXISTS idx_relay_object_state_actor
   ON relay_object_state(actor_id);
 CREATE INDEX IF NOT EXISTS idx_relay_object_state_updated
   ON relay_object_state(updated_at_ms DESC);
+CREATE INDEX IF NOT EXISTS idx_relay_object_state_deleted
+  ON relay_object_state(deleted_at_ms) WHERE deleted_at_ms IS NOT NULL;
 
 CREATE TABLE IF NOT EXISTS relay_chat_envelopes (
   id BIGSERIAL PRIMARY KEY,
@@ -373,6 +518,13 @@ BEGIN
 END;
 $$ LANGUAGE plpgsql;
 
+CREATE OR REPLACE FUNCTION relay_note_sources_delete() RETURNS trigger AS $$
+BEGIN
+  DELETE FROM relay_note_sources WHERE note_id = OLD.note_id;
+  RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
 DO $$
 BEGIN
   IF NOT EXISTS (SELECT 1 FROM pg_trigger WHERE tgname = 'relay_notes_count_insert_tr') THEN
@@ -385,6 +537,11 @@ BEGIN
       AFTER DELETE ON relay_notes
       FOR EACH ROW EXECUTE FUNCTION relay_notes_count_delete();
   END IF;
+  IF NOT EXISTS (SELECT 1 FROM pg_trigger WHERE tgname = 'relay_note_sources_delete_tr') THEN
+    CREATE TRIGGER relay_note_sources_delete_tr
+      AFTER DELETE ON relay_notes
+      FOR EACH ROW EXECUTE FUNCTION relay_note_sources_delete();
+  END IF;
 END;
 $$;
 
@@ -392,6 +549,14 @@ ALTER TABLE relay_notes
   ADD COLUMN IF NOT EXISTS search_tsv tsvector GENERATED ALWAYS AS (
     to_tsvector('simple', coalesce(content_text, '') || ' ' || coalesce(content_html, ''))
   ) STORED;
+
+-- Notes with the body resolved from relay_note_bodies when the hot row keeps an empty note_json.
+CREATE OR REPLACE VIEW relay_notes_full AS
+  SELECT n.note_id, n.actor_id, n.published_ms, n.content_text, n.content_html,
+         COALESCE(NULLIF(n.note_json, ''), b.note_json, '') AS note_json,
+         n.created_at_ms, n.ingested_at_ms, n.search_tsv
+  FROM relay_notes n
+  LEFT JOIN relay_note_bodies b ON b.note_id = n.note_id;
 ALTER TABLE relay_note_tags
   ADD COLUMN IF NOT EXISTS tag_tsv tsvector GENERATED ALWAYS AS (to_tsvector('simple', coalesce(tag, ''))) STORED;
 
@@ -422,6 +587,7 @@ CREATE TABLE IF NOT EXISTS relay_reputation (
   updated_at_ms BIGINT NOT NULL
 );
 CREATE INDEX IF NOT EXISTS idx_relay_reputation_updated ON relay_reputation(updated_at_ms DESC);
+ALTER TABLE relay_reputation ADD COLUMN IF NOT EXISTS pinned INTEGER NOT NULL DEFAULT 0;
 
 CREATE TABLE IF NOT EXISTS relay_outbox_index (
   username TEXT PRIMARY KEY,
@@ -446,3 +612,16 @@ CREATE INDEX IF NOT EXISTS idx_admin_audit_created ON admin_audit(created_at_ms
 ALTER TABLE admin_audit ADD COLUMN IF NOT EXISTS request_id TEXT;
 ALTER TABLE admin_audit ADD COLUMN IF NOT EXISTS correlation_id TEXT;
 ALTER TABLE admin_audit ADD COLUMN IF NOT EXISTS user_agent TEXT;
+
+CREATE TABLE IF NOT EXISTS telemetry_events (
+  id BIGSERIAL PRIMARY KEY,
+  username TEXT NOT NULL,
+  event_type TEXT NOT NULL,
+  level TEXT NOT NULL,
+  message TEXT NOT NULL,
+  fingerprint TEXT NOT NULL,
+  client_ts TEXT NULL,
+  created_at_ms BIGINT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_telemetry_events_created ON telemetry_events(created_at_ms DESC);
+CREATE INDEX IF NOT EXISTS idx_telemetry_events_fingerprint ON telemetry_events(fingerprint, created_at_ms DESC);
@@ -6,12 +6,14 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use aws_sdk_s3::{
-    config::{Credentials, Region},
+    config::{timeout::TimeoutConfig, Credentials, Region},
     primitives::ByteStream,
     Client as S3Client, Config as S3Config,
 };
 use reqwest::Client as HttpClient;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct MediaConfig {
@@ -27,6 +29,8 @@ pub struct MediaConfig {
     pub s3_access_key: Option<String>,
     pub s3_secret_key: Option<String>,
     pub s3_path_style: bool,
+    /// Per-operation budget for S3 calls; WebDAV uses the timeout of the client it is given.
+    pub request_timeout: Duration,
 }
 
 pub struct MediaSaved {
@@ -39,6 +43,8 @@ pub struct MediaSaved {
 pub trait MediaBackend: Send + Sync {
     async fn save_upload(&self, key: &str, media_type: &str, bytes: &[u8]) -> Result<MediaSaved>;
     async fn load(&self, key: &str) -> Result<Vec<u8>>;
+    /// Streams the object into `dest` without holding it in memory; returns its size.
+    async fn load_to_file(&self, key: &str, dest: &Path) -> Result<u64>;
     async fn delete(&self, key: &str) -> Result<()>;
     async fn health_check(&self) -> Result<()>;
 }
@@ -74,6 +80,11 @@ impl MediaBackend for LocalMediaBackend {
         Ok(bytes)
     }
 
+    async fn load_to_file(&self, key: &str, dest: &Path) -> Result<u64> {
+        let path = self.dir.join(key);
+        std::fs::copy(&path, dest).with_context(|| format!("copy media {path:?}"))
+    }
+
     async fn delete(&self, key: &str) -> Result<()> {
         let path = self.dir.join(key);
         if path.exists() {
@@ -155,6 +166,29 @@ impl MediaBackend for WebDavMediaBackend {
         Ok(resp.bytes().await?.to_vec())
     }
 
+    async fn load_to_file(&self, key: &str, dest: &Path) -> Result<u64> {
+        let url = format!("{}/{}", self.base_url, key);
+        let mut req = self.http.get(&url);
+        if let Some(tok) = &self.bearer_token {
+            req = req.header("Authorization", format!("Bearer {}", tok));
+        } else if let (Some(u), Some(p)) = (&self.username, &self.password) {
+            req = req.basic_auth(u, Some(p));
+        }
+        let mut resp = req.send().await.context("webdav get")?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("webdav get failed: {} {}", status, text);
+        }
+        let mut file = std::fs::File::create(dest).context("create media copy")?;
+        let mut size = 0u64;
+        while let Some(chunk) = resp.chunk().await.context("webdav body")? {
+            file.write_all(&chunk).context("write media copy")?;
+            size += chunk.len() as u64;
+        }
+        Ok(size)
+    }
+
     async fn delete(&self, key: &str) -> Result<()> {
         let url = format!("{}/{}", self.base_url, key);
         let mut req = self.http.request(reqwest::Method::DELETE, &url);
@@ -231,6 +265,25 @@ impl MediaBackend for S3MediaBackend {
         Ok(data.into_bytes().to_vec())
     }
 
+    async fn load_to_file(&self, key: &str, dest: &Path) -> Result<u64> {
+        let resp = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .context("s3 get")?;
+        let mut body = resp.body;
+        let mut file = std::fs::File::create(dest).context("create media copy")?;
+        let mut size = 0u64;
+        while let Some(chunk) = body.try_next().await.context("s3 body")? {
+            file.write_all(&chunk).context("write media copy")?;
+            size += chunk.len() as u64;
+        }
+        Ok(size)
+    }
+
     async fn delete(&self, key: &str) -> Result<()> {
         self.client
             .delete_object()
@@ -253,6 +306,22 @@ impl MediaBackend for S3MediaBackend {
     }
 }
 
+/// Writes, reads back and deletes a tiny object under `key` to prove the backend is usable
+/// end to end (credentials, bucket/region, permissions), not just reachable.
+pub async fn probe_roundtrip(backend: &dyn MediaBackend, key: &str) -> Result<()> {
+    let payload = format!("fedi3 media probe {key}");
+    backend
+        .save_upload(key, "text/plain", payload.as_bytes())
+        .await
+        .context("probe write")?;
+    let read = backend.load(key).await.context("probe read");
+    let cleanup = backend.delete(key).await.context("probe delete");
+    if read? != payload.as_bytes() {
+        anyhow::bail!("probe read returned different bytes");
+    }
+    cleanup
+}
+
 pub async fn build_media_backend(
     cfg: &MediaConfig,
     http: HttpClient,
@@ -288,7 +357,12 @@ pub async fn build_media_backend(
             let mut builder = S3Config::builder()
                 .region(Region::new(region))
                 .credentials_provider(credentials)
-                .force_path_style(cfg.s3_path_style);
+                .force_path_style(cfg.s3_path_style)
+                .timeout_config(
+                    TimeoutConfig::builder()
+                        .operation_timeout(cfg.request_timeout)
+                        .build(),
+                );
             if let Some(endpoint) = cfg.s3_endpoint.clone() {
                 builder = builder.endpoint_url(endpoint);
             }
@@ -0,0 +1,123 @@
+/*
+ * SPDX-FileCopyrightText: 2026 RedHunt07 - FEDI3 Project
+ * SPDX-License-Identifier: AGPL-3.0-only
+ */
+
+//! Minimal `multipart/form-data` parsing for request bodies that are already buffered.
+
+use anyhow::Result;
+use bytes::Bytes;
+
+/// Upper bound on parts accepted in one form; uploads only need the file plus a few fields.
+pub const MAX_FORM_PARTS: usize = 16;
+/// Upper bound on a single part's header block.
+const MAX_PART_HEADER_BYTES: usize = 8 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct FormPart {
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub data: Bytes,
+}
+
+impl FormPart {
+    pub fn text(&self) -> Option<&str> {
+        std::str::from_utf8(&self.data).ok()
+    }
+}
+
+/// Boundary from a `multipart/form-data; boundary=...` content type, if it is one.
+pub fn form_data_boundary(content_type: &str) -> Option<String> {
+    let mut params = content_type.split(';');
+    let mime = params.next()?.trim();
+    if !mime.eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params
+        .filter_map(|p| p.split_once('='))
+        .find(|(k, _)| k.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, v)| v.trim().trim_matches('"').to_string())
+        .filter(|b| !b.is_empty() && b.len() <= 70)
+}
+
+/// Splits `body` into its form parts. Part bodies are zero-copy slices of `body`.
+pub fn parse_form_data(body: &Bytes, boundary: &str) -> Result<Vec<FormPart>> {
+    let delimiter = format!("--{boundary}");
+    let delimiter = delimiter.as_bytes();
+    let mut pos = find(body, delimiter, 0)
+        .ok_or_else(|| anyhow::anyhow!("multipart boundary not found"))?
+        + delimiter.len();
+    let mut parts = Vec::new();
+    loop {
+        if body[pos..].starts_with(b"--") {
+            return Ok(parts);
+        }
+        if !body[pos..].starts_with(b"\r\n") {
+            anyhow::bail!("malformed multipart delimiter");
+        }
+        pos += 2;
+        if parts.len() >= MAX_FORM_PARTS {
+            anyhow::bail!("too many multipart parts");
+        }
+        let header_end = find(body, b"\r\n\r\n", pos)
+            .filter(|end| end - pos <= MAX_PART_HEADER_BYTES)
+            .ok_or_else(|| anyhow::anyhow!("malformed multipart part headers"))?;
+        let headers = std::str::from_utf8(&body[pos..header_end])
+            .map_err(|_| anyhow::anyhow!("non-utf8 multipart headers"))?;
+        let data_start = header_end + 4;
+        let mut closing = Vec::with_capacity(delimiter.len() + 2);
+        closing.extend_from_slice(b"\r\n");
+        closing.extend_from_slice(delimiter);
+        let data_end = find(body, &closing, data_start)
+            .ok_or_else(|| anyhow::anyhow!("unterminated multipart part"))?;
+        parts.push(part_from_headers(
+            headers,
+            body.slice(data_start..data_end),
+        )?);
+        pos = data_end + closing.len();
+    }
+}
+
+fn part_from_headers(headers: &str, data: Bytes) -> Result<FormPart> {
+    let mut name = None;
+    let mut filename = None;
+    let mut content_type = None;
+    for line in headers.split("\r\n") {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim();
+        if key.eq_ignore_ascii_case("content-disposition") {
+            for param in value.split(';').skip(1) {
+                let Some((k, v)) = param.split_once('=') else {
+                    continue;
+                };
+                let v = v.trim().trim_matches('"').to_string();
+                match k.trim().to_ascii_lowercase().as_str() {
+                    "name" => name = Some(v),
+                    "filename" => filename = Some(v),
+                    _ => {}
+                }
+            }
+        } else if key.eq_ignore_ascii_case("content-type") {
+            content_type = Some(value.trim().to_string());
+        }
+    }
+    Ok(FormPart {
+        name: name.ok_or_else(|| anyhow::anyhow!("multipart part without name"))?,
+        filename: filename.filter(|f| !f.is_empty()),
+        content_type: content_type.filter(|c| !c.is_empty()),
+        data,
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if from > haystack.len() || needle.is_empty() {
+        return None;
+    }
+    haystack[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|i| i + from)
+}
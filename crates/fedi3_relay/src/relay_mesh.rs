@@ -29,8 +29,10 @@ use crate::relay_notes::{
 };
 use crate::{now_ms, relay_p2p_infra_multiaddrs, AppState, RelayTelemetry};
 
-const RELAY_REPUTATION_MIN_SCORE: i32 = -3;
-const RELAY_REPUTATION_MAX_SCORE: i32 = 10;
+pub(crate) const RELAY_REPUTATION_MIN_SCORE: i32 = -3;
+pub(crate) const RELAY_REPUTATION_MAX_SCORE: i32 = 10;
+/// Lowest score automatic updates can reach.
+pub(crate) const RELAY_REPUTATION_FLOOR: i32 = -10;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 struct RelayMeshSyncRequest {
@@ -289,6 +291,7 @@ where
 
 struct PendingSync {
     relay_url: String,
+    base_domain: Option<String>,
     peer_id: PeerId,
     last_seen: i64,
     max_seen: i64,
@@ -418,6 +421,15 @@ async fn run_relay_mesh(state: AppState) -> Result<()> {
                 match ev {
                     SwarmEvent::NewListenAddr { address, .. } => {
                         info!(%peer_id, %address, "relay mesh listening");
+                        let mut addrs = state.relay_mesh_listen_addrs.write().await;
+                        let address = address.to_string();
+                        if !addrs.contains(&address) {
+                            addrs.push(address);
+                        }
+                    }
+                    SwarmEvent::ExpiredListenAddr { address, .. } => {
+                        let address = address.to_string();
+                        state.relay_mesh_listen_addrs.write().await.retain(|a| *a != address);
                     }
                     SwarmEvent::Dialing { peer_id: target_peer_id, connection_id } => {
                         if mesh_diag_enabled(&cfg, diag_tick) {
@@ -540,8 +552,8 @@ async fn handle_sync_request(
     }
 
     let limit = req.limit.min(cfg.sync_limit).max(1);
-    let db = state.db.lock().await;
-    let note_page = db
+    let note_page = state
+        .db
         .list_relay_notes_sync(limit, req.since, req.cursor)
         .unwrap_or_else(|_| crate::CollectionPage {
             total: 0,
@@ -557,10 +569,13 @@ async fn handle_sync_request(
                 .map(|note| RelaySyncNoteItem {
                     note,
                     created_at_ms,
+                    content_hash: None,
+                    signature_b64: None,
                 })
         })
         .collect::<Vec<_>>();
-    let media_page = db
+    let media_page = state
+        .db
         .list_relay_media_sync(limit, req.since, req.cursor)
         .unwrap_or_else(|_| crate::CollectionPage {
             total: 0,
@@ -580,7 +595,8 @@ async fn handle_sync_request(
             created_at_ms: item.created_at_ms,
         })
         .collect::<Vec<_>>();
-    let actor_page = db
+    let actor_page = state
+        .db
         .list_relay_actor_sync(limit, req.since, req.cursor)
         .unwrap_or_else(|_| crate::CollectionPage {
             total: 0,
@@ -624,7 +640,7 @@ async fn handle_sync_request(
 
     let mut bundle = build_sync_bundle(state, notes, media, actors, next).await;
     if !bundle.relay_url.is_empty() {
-        if let Ok((_, sk_b64)) = db.load_or_create_signing_keypair_b64() {
+        if let Ok((_, sk_b64)) = state.db.load_or_create_signing_keypair_b64() {
             if let Ok(sig) = sign_bundle_b64(&bundle, &sk_b64) {
                 bundle.signature_b64 = Some(sig);
             }
@@ -660,15 +676,14 @@ async fn handle_sync_response(
     }
 
     let signature_ok = {
-        let db = state.db.lock().await;
-        let pk_b64 = db.get_relay_pubkey_b64(&pend.relay_url).ok().flatten();
-        if response.signature_b64.is_none() {
-            false
-        } else if let Some(pk_b64) = pk_b64 {
-            verify_bundle_signature(&response, &pk_b64).is_ok()
-        } else {
-            false
-        }
+        let keys = state
+            .db
+            .get_relay_pubkeys_b64(&pend.relay_url)
+            .unwrap_or_default();
+        response.signature_b64.is_some()
+            && keys
+                .iter()
+                .any(|pk_b64| verify_bundle_signature(&response, pk_b64).is_ok())
     };
 
     if !signature_ok {
@@ -700,17 +715,49 @@ async fn handle_sync_response(
 
     let mut item_count = 0usize;
     if !notes.is_empty() || !media.is_empty() || !actors.is_empty() {
-        let db = state.db.lock().await;
+        // The whole bundle is already signed with the relay's pinned key, so per-note
+        // signatures add nothing here; only the actor-host check applies.
+        let verify = match state.cfg.relay_sync_verify {
+            crate::RelaySyncVerify::Off => crate::RelaySyncVerify::Off,
+            _ => crate::RelaySyncVerify::Host,
+        };
+        let mut rejected = 0usize;
+        let mut note_ids = Vec::new();
         for item in notes {
             item_count += 1;
             if item.created_at_ms > pend.max_seen {
                 pend.max_seen = item.created_at_ms;
             }
+            if let Err(reason) = crate::check_synced_note(
+                verify,
+                &item,
+                &pend.relay_url,
+                pend.base_domain.as_deref(),
+                &[],
+            ) {
+                rejected += 1;
+                debug!(relay_url = %pend.relay_url, reason, "rejected mesh-synced note");
+                continue;
+            }
+            if crate::is_blocked_note(&state.cfg, &item.note) {
+                continue;
+            }
             if let Some(mut indexed) = note_to_index(&item.note) {
                 indexed.created_at_ms = item.created_at_ms;
-                let _ = db.upsert_relay_note(&indexed);
+                if state.db.upsert_relay_note(&indexed).is_ok() {
+                    note_ids.push(indexed.note_id);
+                }
             }
         }
+        let ids: Vec<&str> = note_ids.iter().map(String::as_str).collect();
+        let _ = state.db.record_note_sources(&ids, &pend.relay_url);
+        if rejected > 0 {
+            warn!(
+                relay_url = %pend.relay_url,
+                rejected,
+                "relay mesh sync rejected notes failing verification"
+            );
+        }
         for item in media {
             item_count += 1;
             if item.created_at_ms > pend.max_seen {
@@ -725,20 +772,23 @@ async fn handle_sync_response(
                 blurhash: item.blurhash,
                 created_at_ms: item.created_at_ms,
             };
-            let _ = db.upsert_relay_media(&idx);
+            let _ = state.db.upsert_relay_media(&idx);
         }
         for item in actors {
             item_count += 1;
             if item.updated_at_ms > pend.max_seen {
                 pend.max_seen = item.updated_at_ms;
             }
+            if crate::is_blocked_actor_url(&state.cfg, &item.actor_url) {
+                continue;
+            }
             let idx = RelayActorIndex {
                 actor_url: item.actor_url,
                 username: item.username,
                 actor_json: item.actor_json,
                 updated_at_ms: item.updated_at_ms,
             };
-            let _ = db.upsert_relay_actor(&idx);
+            let _ = state.db.upsert_relay_actor(&idx);
         }
         info!(
             relay_url = %pend.relay_url,
@@ -761,6 +811,7 @@ async fn handle_sync_response(
             req_id,
             PendingSync {
                 relay_url: pend.relay_url,
+                base_domain: pend.base_domain,
                 peer_id: pend.peer_id,
                 last_seen: pend.last_seen,
                 max_seen: pend.max_seen,
@@ -771,9 +822,8 @@ async fn handle_sync_response(
 
     inflight_relays.remove(&pend.relay_url);
     if pend.max_seen > pend.last_seen {
-        let db = state.db.lock().await;
         let key = format!("relay_sync_last_ms:{}", pend.relay_url);
-        let _ = db.relay_meta_set(&key, &pend.max_seen.to_string());
+        let _ = state.db.relay_meta_set(&key, &pend.max_seen.to_string());
     }
 }
 
@@ -793,9 +843,8 @@ async fn queue_sync_requests(
         .clone()
         .unwrap_or_default();
     let (relays, last_map) = {
-        let db = state.db.lock().await;
-        let relays = db.list_relays(500).unwrap_or_default();
-        let last = db.list_relay_sync_state().unwrap_or_default();
+        let relays = state.db.list_relays(500).unwrap_or_default();
+        let last = state.db.list_relay_sync_state().unwrap_or_default();
         let mut map = HashMap::new();
         for (relay_url, last_ms) in last {
             map.insert(relay_url, last_ms);
@@ -803,7 +852,7 @@ async fn queue_sync_requests(
         (relays, map)
     };
 
-    for (relay_url, _base_domain, _last_seen, telemetry_json, _sig) in relays {
+    for (relay_url, base_domain, _last_seen, telemetry_json, _sig) in relays {
         if inflight_relays.contains(&relay_url) {
             continue;
         }
@@ -892,6 +941,7 @@ async fn queue_sync_requests(
             req_id,
             PendingSync {
                 relay_url: relay_url.clone(),
+                base_domain: base_domain.clone(),
                 peer_id,
                 last_seen: since.unwrap_or(0),
                 max_seen: since.unwrap_or(0),
@@ -924,9 +974,8 @@ async fn queue_sync_requests_for_peer(
     }
 
     let (relays, last_map) = {
-        let db = state.db.lock().await;
-        let relays = db.list_relays(500).unwrap_or_default();
-        let last = db.list_relay_sync_state().unwrap_or_default();
+        let relays = state.db.list_relays(500).unwrap_or_default();
+        let last = state.db.list_relay_sync_state().unwrap_or_default();
         let mut map = HashMap::new();
         for (relay_url, last_ms) in last {
             map.insert(relay_url, last_ms);
@@ -934,7 +983,7 @@ async fn queue_sync_requests_for_peer(
         (relays, map)
     };
 
-    for (relay_url, _base_domain, _last_seen, telemetry_json, _sig) in relays {
+    for (relay_url, base_domain, _last_seen, telemetry_json, _sig) in relays {
         if inflight_relays.contains(&relay_url) {
             continue;
         }
@@ -967,6 +1016,7 @@ async fn queue_sync_requests_for_peer(
             req_id,
             PendingSync {
                 relay_url: relay_url.clone(),
+                base_domain: base_domain.clone(),
                 peer_id,
                 last_seen: since.unwrap_or(0),
                 max_seen: since.unwrap_or(0),
@@ -1052,6 +1102,77 @@ fn peer_direct_dial_addrs(relay_url: &str, peer_id: PeerId) -> Vec<Multiaddr> {
     out
 }
 
+/// Addresses a client can reach from outside: not loopback, unspecified, private or
+/// link-local.
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            !(v4.is_loopback() || v4.is_unspecified() || v4.is_private() || v4.is_link_local())
+        }
+        IpAddr::V6(v6) => {
+            let seg = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || (seg & 0xfe00) == 0xfc00
+                || (seg & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// Dialable forms of the mesh's bound listen addresses: the relay's public host with each
+/// bound port first (a `/tcp/0` listener only learns its port once bound), then the bound
+/// addresses that are publicly reachable as they are. Each ends with `/p2p/<peer_id>`.
+pub(crate) fn advertised_mesh_multiaddrs(
+    bound: &[String],
+    public_host: Option<&str>,
+    peer_id: &str,
+) -> Vec<String> {
+    let Ok(peer) = peer_id.parse::<PeerId>() else {
+        return Vec::new();
+    };
+    let host_proto = public_host.map(|h| match h.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => libp2p::multiaddr::Protocol::Ip4(ip),
+        Ok(IpAddr::V6(ip)) => libp2p::multiaddr::Protocol::Ip6(ip),
+        Err(_) => libp2p::multiaddr::Protocol::Dns4(h.to_string().into()),
+    });
+    let mut on_host = Vec::new();
+    let mut direct = Vec::new();
+    for addr in bound.iter().filter_map(|a| a.parse::<Multiaddr>().ok()) {
+        let mut parts = addr.iter();
+        let ip = match parts.next() {
+            Some(libp2p::multiaddr::Protocol::Ip4(ip)) => IpAddr::V4(ip),
+            Some(libp2p::multiaddr::Protocol::Ip6(ip)) => IpAddr::V6(ip),
+            _ => continue,
+        };
+        let rest: Vec<_> = parts.collect();
+        if let Some(host) = host_proto.clone() {
+            let public = std::iter::once(host)
+                .chain(rest.iter().cloned())
+                .collect::<Multiaddr>()
+                .with(libp2p::multiaddr::Protocol::P2p(peer))
+                .to_string();
+            if !on_host.contains(&public) {
+                on_host.push(public);
+            }
+        }
+        if is_public_ip(ip) {
+            let raw = addr
+                .with(libp2p::multiaddr::Protocol::P2p(peer))
+                .to_string();
+            if !direct.contains(&raw) {
+                direct.push(raw);
+            }
+        }
+    }
+    on_host.extend(
+        direct
+            .into_iter()
+            .filter(|a| !on_host.contains(a))
+            .collect::<Vec<_>>(),
+    );
+    on_host
+}
+
 fn is_supported_mesh_addr(addr: &Multiaddr, enable_quic: bool) -> bool {
     let mut has_tcp = false;
     for p in addr.iter() {
@@ -1107,10 +1228,7 @@ fn synthesize_peer_hint_telemetry(relay_url: &str, peer_id: &str) -> String {
 }
 
 async fn build_peer_hints(state: &AppState, self_relay_url: &str) -> Vec<RelayMeshPeerHint> {
-    let relays = {
-        let db = state.db.lock().await;
-        db.list_relays(200).unwrap_or_default()
-    };
+    let relays = state.db.list_relays(200).unwrap_or_default();
     let self_url = self_relay_url.trim_end_matches('/');
     let mut out = Vec::new();
     for (relay_url, _base_domain, _last_seen, telemetry_json, _sig) in relays {
@@ -1140,7 +1258,6 @@ async fn ingest_peer_hints(state: &AppState, hints: &[RelayMeshPeerHint]) -> usi
         .map(|v| v.trim_end_matches('/').to_string())
         .unwrap_or_default();
     let mut discovered = 0usize;
-    let mut db = state.db.lock().await;
     for hint in hints.iter().take(200) {
         let relay_url = hint.relay_url.trim_end_matches('/');
         let peer_id = hint.peer_id.trim();
@@ -1154,7 +1271,8 @@ async fn ingest_peer_hints(state: &AppState, hints: &[RelayMeshPeerHint]) -> usi
             .ok()
             .and_then(|u| u.host_str().map(|h| h.to_string()));
         let telemetry_json = Some(synthesize_peer_hint_telemetry(relay_url, peer_id));
-        if db
+        if state
+            .db
             .upsert_relay(relay_url, base_domain, telemetry_json, None)
             .is_ok()
         {
@@ -1193,8 +1311,7 @@ async fn build_sync_bundle(
 async fn build_empty_bundle(state: &AppState) -> RelaySyncBundle {
     let mut bundle = build_sync_bundle(state, Vec::new(), Vec::new(), Vec::new(), None).await;
     if !bundle.relay_url.is_empty() {
-        let db = state.db.lock().await;
-        if let Ok((_, sk_b64)) = db.load_or_create_signing_keypair_b64() {
+        if let Ok((_, sk_b64)) = state.db.load_or_create_signing_keypair_b64() {
             if let Ok(sig) = sign_bundle_b64(&bundle, &sk_b64) {
                 bundle.signature_b64 = Some(sig);
             }
@@ -1218,10 +1335,7 @@ async fn build_signed_mesh_request(
     if self_relay_url.is_empty() {
         return Err(anyhow::anyhow!("missing public_url"));
     }
-    let (self_pk_b64, self_sk_b64) = {
-        let db = state.db.lock().await;
-        db.load_or_create_signing_keypair_b64()?
-    };
+    let (self_pk_b64, self_sk_b64) = state.db.load_or_create_signing_keypair_b64()?;
     let mut req = RelayMeshSyncRequest {
         since,
         cursor,
@@ -1262,27 +1376,41 @@ async fn verify_mesh_request(
         return Err(anyhow::anyhow!("missing signature"));
     }
 
-    let pk_b64 = {
-        let db = state.db.lock().await;
-        db.get_relay_pubkey_b64(relay_url).ok().flatten()
-    };
+    let keys = state
+        .db
+        .get_relay_pubkeys_b64(relay_url)
+        .unwrap_or_default();
 
-    let pk_b64 = if let Some(pk_b64) = pk_b64 {
-        pk_b64
+    let keys = if !keys.is_empty() {
+        keys
     } else if let Some(pk_b64) = req
         .sign_pubkey_b64
         .as_deref()
         .map(str::trim)
         .filter(|v| !v.is_empty())
     {
-        let mut db = state.db.lock().await;
-        let _ = db.upsert_relay(relay_url, None, None, Some(pk_b64.to_string()));
-        pk_b64.to_string()
+        let _ = state
+            .db
+            .upsert_relay(relay_url, None, None, Some(pk_b64.to_string()));
+        vec![pk_b64.to_string()]
     } else {
         return Err(anyhow::anyhow!("missing sign_pubkey_b64"));
     };
 
-    verify_mesh_request_signature(req, &pk_b64)?;
+    // During a key rotation overlap either key may have signed the request.
+    let mut last_err = None;
+    for pk_b64 in &keys {
+        match verify_mesh_request_signature(req, pk_b64) {
+            Ok(()) => {
+                last_err = None;
+                break;
+            }
+            Err(e) => last_err = Some(e),
+        }
+    }
+    if let Some(e) = last_err {
+        return Err(e);
+    }
     update_reputation(state, relay_url, 1, cfg.reputation_ttl_ms).await;
     Ok(())
 }
@@ -1387,7 +1515,7 @@ async fn reputation_allows(state: &AppState, relay_url: &str, retention_ms: i64)
     let now = now_ms();
     let mut rep = state.relay_reputation.lock().await;
     if retention_ms > 0 {
-        rep.retain(|_, v| now.saturating_sub(v.last_ms) <= retention_ms);
+        rep.retain(|_, v| v.pinned || now.saturating_sub(v.last_ms) <= retention_ms);
     }
     let key = relay_url.trim_end_matches('/');
     rep.get(key)
@@ -1399,19 +1527,22 @@ async fn update_reputation(state: &AppState, relay_url: &str, delta: i32, retent
     let now = now_ms();
     let mut rep = state.relay_reputation.lock().await;
     if retention_ms > 0 {
-        rep.retain(|_, v| now.saturating_sub(v.last_ms) <= retention_ms);
+        rep.retain(|_, v| v.pinned || now.saturating_sub(v.last_ms) <= retention_ms);
     }
     let key = relay_url.trim_end_matches('/').to_string();
     let entry = rep.entry(key.clone()).or_insert(crate::RelayReputation {
         score: 0,
         last_ms: now,
+        pinned: false,
     });
+    if entry.pinned {
+        return;
+    }
     entry.score = (entry.score + delta)
         .min(RELAY_REPUTATION_MAX_SCORE)
-        .max(-10);
+        .max(RELAY_REPUTATION_FLOOR);
     entry.last_ms = now;
     let score = entry.score;
     drop(rep);
-    let db = state.db.lock().await;
-    let _ = db.upsert_relay_reputation(&key, score, now);
+    let _ = state.db.upsert_relay_reputation(&key, score, now);
 }
@@ -9,6 +9,12 @@ use crate::now_ms;
 pub struct RelaySyncNoteItem {
     pub note: serde_json::Value,
     pub created_at_ms: i64,
+    /// sha256 (hex) of the serialized note, set by relays that sign their sync pages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Serving relay's ed25519 signature over `content_hash` and `created_at_ms`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature_b64: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -0,0 +1,209 @@
+/*
+ * SPDX-FileCopyrightText: 2026 RedHunt07 - FEDI3 Project
+ * SPDX-License-Identifier: AGPL-3.0-only
+ */
+
+use anyhow::{Context, Result};
+use reqwest::Client as HttpClient;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Sanitized client telemetry, as handed to every configured sink.
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryEvent {
+    pub handle: String,
+    pub event_type: String,
+    pub level: String,
+    pub mode: String,
+    pub ts: String,
+    pub message: String,
+    pub stack: String,
+    pub fingerprint: String,
+}
+
+pub trait TelemetrySink: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Queues the event without waiting for delivery; `false` when the sink's queue is full.
+    fn submit(&self, event: &TelemetryEvent) -> bool;
+}
+
+#[derive(Debug, Clone)]
+pub enum HttpSinkTarget {
+    /// POST the event as JSON, with an optional bearer token.
+    Webhook { url: String, token: Option<String> },
+    /// OTLP/HTTP JSON logs (`<endpoint>/v1/logs`).
+    Otlp { logs_url: String },
+    /// Sentry store API derived from a DSN.
+    Sentry { store_url: String, auth: String },
+}
+
+impl HttpSinkTarget {
+    /// `https://<public_key>@<host>/<project_id>` -> store endpoint and auth header.
+    pub fn sentry_from_dsn(dsn: &str) -> Result<Self> {
+        let url = reqwest::Url::parse(dsn.trim()).context("invalid sentry dsn")?;
+        let key = url.username();
+        if key.is_empty() {
+            anyhow::bail!("sentry dsn has no public key");
+        }
+        let host = url.host_str().context("sentry dsn has no host")?;
+        let path = url.path().trim_matches('/');
+        let (prefix, project) = match path.rsplit_once('/') {
+            Some((prefix, project)) => (format!("/{prefix}"), project),
+            None => (String::new(), path),
+        };
+        if project.is_empty() {
+            anyhow::bail!("sentry dsn has no project id");
+        }
+        let port = url.port().map(|p| format!(":{p}")).unwrap_or_default();
+        Ok(HttpSinkTarget::Sentry {
+            store_url: format!(
+                "{}://{host}{port}{prefix}/api/{project}/store/",
+                url.scheme()
+            ),
+            auth: format!(
+                "Sentry sentry_version=7, sentry_key={key}, sentry_client=fedi3-relay/{}",
+                env!("CARGO_PKG_VERSION")
+            ),
+        })
+    }
+
+    pub fn otlp(endpoint: &str) -> Self {
+        let base = endpoint.trim().trim_end_matches('/');
+        let logs_url = if base.ends_with("/v1/logs") {
+            base.to_string()
+        } else {
+            format!("{base}/v1/logs")
+        };
+        HttpSinkTarget::Otlp { logs_url }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            HttpSinkTarget::Webhook { .. } => "webhook",
+            HttpSinkTarget::Otlp { .. } => "otlp",
+            HttpSinkTarget::Sentry { .. } => "sentry",
+        }
+    }
+
+    fn request(&self, http: &HttpClient, event: &TelemetryEvent) -> reqwest::RequestBuilder {
+        match self {
+            HttpSinkTarget::Webhook { url, token } => {
+                let req = http.post(url).json(event);
+                match token {
+                    Some(t) => req.bearer_auth(t),
+                    None => req,
+                }
+            }
+            HttpSinkTarget::Otlp { logs_url } => http.post(logs_url).json(&otlp_payload(event)),
+            HttpSinkTarget::Sentry { store_url, auth } => http
+                .post(store_url)
+                .header("X-Sentry-Auth", auth)
+                .json(&sentry_payload(event)),
+        }
+    }
+}
+
+/// Webhook, OTLP and Sentry sinks: a bounded queue drained by one worker task.
+pub struct HttpTelemetrySink {
+    name: &'static str,
+    tx: mpsc::Sender<TelemetryEvent>,
+}
+
+impl HttpTelemetrySink {
+    pub fn spawn(target: HttpSinkTarget, http: HttpClient, queue_max: usize) -> Self {
+        let name = target.name();
+        let (tx, mut rx) = mpsc::channel::<TelemetryEvent>(queue_max.max(1));
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                match target.request(&http, &event).send().await {
+                    Ok(r) if r.status().is_success() => {}
+                    Ok(r) => {
+                        warn!(sink = name, status = %r.status(), "telemetry sink rejected event")
+                    }
+                    Err(e) => warn!(sink = name, "telemetry sink send failed: {e}"),
+                }
+            }
+        });
+        Self { name, tx }
+    }
+}
+
+impl TelemetrySink for HttpTelemetrySink {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn submit(&self, event: &TelemetryEvent) -> bool {
+        self.tx.try_send(event.clone()).is_ok()
+    }
+}
+
+fn severity(level: &str) -> (u8, &'static str) {
+    match level {
+        "crash" => (21, "FATAL"),
+        "error" => (17, "ERROR"),
+        "warn" => (13, "WARN"),
+        _ => (9, "INFO"),
+    }
+}
+
+fn otlp_payload(event: &TelemetryEvent) -> serde_json::Value {
+    let (severity_number, severity_text) = severity(&event.level);
+    let time_unix_nano = chrono::DateTime::parse_from_rfc3339(&event.ts)
+        .ok()
+        .and_then(|t| t.timestamp_nanos_opt())
+        .unwrap_or_else(|| chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0));
+    let attr = |key: &str, value: &str| serde_json::json!({ "key": key, "value": { "stringValue": value } });
+    serde_json::json!({
+        "resourceLogs": [{
+            "resource": {
+                "attributes": [
+                    attr("service.name", "fedi3-client"),
+                    attr("service.namespace", "fedi3"),
+                ]
+            },
+            "scopeLogs": [{
+                "scope": { "name": "fedi3_relay.client_telemetry" },
+                "logRecords": [{
+                    "timeUnixNano": time_unix_nano.to_string(),
+                    "severityNumber": severity_number,
+                    "severityText": severity_text,
+                    "body": { "stringValue": event.message },
+                    "attributes": [
+                        attr("fedi3.user", &event.handle),
+                        attr("fedi3.event_type", &event.event_type),
+                        attr("fedi3.mode", &event.mode),
+                        attr("fedi3.fingerprint", &event.fingerprint),
+                        attr("exception.stacktrace", &event.stack),
+                    ]
+                }]
+            }]
+        }]
+    })
+}
+
+fn sentry_payload(event: &TelemetryEvent) -> serde_json::Value {
+    let level = match event.level.as_str() {
+        "crash" => "fatal",
+        "error" => "error",
+        "warn" => "warning",
+        _ => "info",
+    };
+    let event_id = format!("{:032x}", rand::random::<u128>());
+    serde_json::json!({
+        "event_id": event_id,
+        "timestamp": event.ts,
+        "platform": "other",
+        "level": level,
+        "logger": "fedi3.client",
+        "message": { "formatted": event.message },
+        "fingerprint": [event.fingerprint],
+        "tags": {
+            "event_type": event.event_type,
+            "mode": event.mode,
+        },
+        "user": { "username": event.handle },
+        "extra": { "stack": event.stack },
+    })
+}